@@ -1,5 +1,8 @@
 pub mod info;
 pub mod mp3;
+pub mod multi_format;
+pub mod reader;
+pub mod token_store;
 
 pub use anni_provider::{AnniProvider, ProviderError};
 pub use onedrive_api;
@@ -10,24 +13,47 @@ use std::{
     fmt::Display,
     num::NonZeroU8,
     sync::{atomic::AtomicU64, Arc},
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anni_provider::{AudioInfo, AudioResourceReader, Range, ResourceReader};
 use onedrive_api::{
     option::ObjectOption,
     resource::{DriveItem, DriveItemField},
-    Auth, DriveLocation, ItemLocation, OneDrive, Permission,
+    Auth, DriveLocation, FileName, ItemLocation, OneDrive, Permission,
 };
-use reqwest::{
-    header::{CONTENT_RANGE, RANGE},
-    redirect::Policy,
-    Client, ClientBuilder,
-};
-use tokio::sync::RwLock;
+use reqwest::{redirect::Policy, Client, ClientBuilder};
+use tokio::{io::AsyncReadExt, sync::RwLock};
 use tokio_stream::StreamExt;
 use tokio_util::io::StreamReader;
 
+use crate::{reader::ChunkedReader, token_store::TokenStore};
+
+/// Files at or under this size are uploaded in a single `PUT .../content` request.
+const SIMPLE_UPLOAD_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// Chunk size used for resumable upload sessions. Must be a multiple of 320 KiB.
+const UPLOAD_CHUNK_SIZE: u64 = 320 * 1024 * 10;
+
+/// How many times a single chunk is retried after a server error before giving up.
+const UPLOAD_CHUNK_RETRIES: usize = 3;
+
+/// How long a cached [`CachedItem`] is served before being re-resolved. Kept
+/// conservatively below the roughly one-hour lifetime of OneDrive's
+/// pre-authenticated download URLs.
+const ITEM_CACHE_TTL: Duration = Duration::from_secs(45 * 60);
+
+/// A cached `get_item` lookup: the pre-authenticated download URL, size and
+/// (if present) audio duration of an item, keyed by path in
+/// [`OneDriveProvider`]'s `item_cache`.
+#[derive(Debug, Clone)]
+struct CachedItem {
+    download_url: String,
+    size: u64,
+    duration: Option<u64>,
+    fetched_at: Instant,
+}
+
 #[derive(Debug, Clone)]
 pub struct ClientInfo {
     pub refresh_token: String,
@@ -53,12 +79,21 @@ pub struct OneDriveClient {
     expire: AtomicU64,
     client_info: RwLock<ClientInfo>,
     client: Client,
+    token_store: Option<Arc<dyn TokenStore>>,
 }
 
 impl OneDriveClient {
     /// Creates a new client.
     /// To get the required client_id, refresh_token and client_secret, you can take [rclone's doc](https://rclone.org/onedrive/#getting-your-own-client-id-and-key) as a reference.
-    pub async fn new(client_id: String, info: ClientInfo) -> Result<Self, Error> {
+    ///
+    /// If `token_store` is given, a previously saved refresh token takes precedence
+    /// over `info.refresh_token`, and every subsequent rotation is persisted to it so
+    /// a process restart doesn't lose access.
+    pub async fn new(
+        client_id: String,
+        info: ClientInfo,
+        token_store: Option<Arc<dyn TokenStore>>,
+    ) -> Result<Self, Error> {
         let client = ClientBuilder::new()
             .redirect(Policy::none())
             .build()
@@ -69,11 +104,19 @@ impl OneDriveClient {
             Permission::new_read().offline_access(true),
             "",
         );
+        let loaded_token = match &token_store {
+            Some(store) => store.load().await,
+            None => None,
+        };
+        let refresh_token = loaded_token.unwrap_or_else(|| info.refresh_token.clone());
         let token = auth
-            .login_with_refresh_token(&info.refresh_token, Some(&info.client_secret))
+            .login_with_refresh_token(&refresh_token, Some(&info.client_secret))
             .await?;
         let access_token = token.access_token;
         let refresh_token = token.refresh_token.expect("Fail to get refresh token");
+        if let Some(store) = &token_store {
+            store.save(&refresh_token).await;
+        }
         let expire = (Duration::from_secs(token.expires_in_secs) + now()).as_secs();
         let drive = OneDrive::new_with_client(client.clone(), access_token, info.location.clone());
 
@@ -86,6 +129,7 @@ impl OneDriveClient {
             }),
             expire: AtomicU64::new(expire),
             client,
+            token_store,
         })
     }
 
@@ -126,6 +170,10 @@ impl OneDriveClient {
             OneDrive::new_with_client(self.client.clone(), access_token, info.location.clone());
         *self.drive.write().await = drive;
 
+        if let Some(store) = &self.token_store {
+            store.save(&refresh_token).await;
+        }
+
         self.set_expire(expire.as_secs());
         info.refresh_token = refresh_token;
 
@@ -174,6 +222,148 @@ impl OneDriveClient {
             .transpose()
             .unwrap()
     }
+
+    /// Starts tracking changes under `item` from scratch, for an initial full sync.
+    pub async fn track_changes_from_initial(
+        &self,
+        item: ItemLocation<'_>,
+    ) -> Result<onedrive_api::ListChildrenFetcher, onedrive_api::Error> {
+        #[cfg(feature = "auto-refresh")]
+        self.refresh_if_expired().await?;
+        self.drive
+            .read()
+            .await
+            .track_changes_from_initial(item)
+            .await
+    }
+
+    /// Resumes tracking changes from a delta link previously returned by
+    /// [`fetch_delta_url`](Self::fetch_delta_url).
+    pub async fn track_changes_from_delta_url(
+        &self,
+        delta_link: &str,
+    ) -> Result<onedrive_api::ListChildrenFetcher, onedrive_api::Error> {
+        #[cfg(feature = "auto-refresh")]
+        self.refresh_if_expired().await?;
+        self.drive
+            .read()
+            .await
+            .track_changes_from_delta_url(delta_link)
+            .await
+    }
+
+    /// Fetches the next page of a delta sync, or `None` once exhausted.
+    pub async fn fetch_delta_page(
+        &self,
+        fetcher: &mut onedrive_api::ListChildrenFetcher,
+    ) -> Result<Option<Vec<DriveItem>>, onedrive_api::Error> {
+        #[cfg(feature = "auto-refresh")]
+        self.refresh_if_expired().await?;
+        fetcher.fetch_next_page(&self.client).await
+    }
+
+    /// Fetches the delta link to resume from after a fully-drained sync.
+    pub async fn fetch_delta_url(
+        &self,
+        fetcher: &onedrive_api::ListChildrenFetcher,
+    ) -> Result<String, onedrive_api::Error> {
+        fetcher.fetch_delta_url(&self.client).await
+    }
+
+    /// Creates a folder named `name` under `parent`. If a folder of that name
+    /// already exists, the drive's conflict error is returned unchanged.
+    pub async fn create_directory(
+        &self,
+        parent: ItemLocation<'_>,
+        name: &str,
+    ) -> Result<DriveItem, onedrive_api::Error> {
+        #[cfg(feature = "auto-refresh")]
+        self.refresh_if_expired().await?;
+        let name = FileName::new(name).expect("invalid directory name");
+        self.drive.read().await.create_folder(parent, name).await
+    }
+
+    /// Uploads `reader` to `item`, picking a simple upload or a resumable upload
+    /// session depending on `size`.
+    pub async fn put_file(
+        &self,
+        item: ItemLocation<'_>,
+        reader: impl tokio::io::AsyncRead + Unpin,
+        size: u64,
+    ) -> Result<DriveItem, Error> {
+        if size <= SIMPLE_UPLOAD_THRESHOLD {
+            self.put_file_simple(item, reader, size).await
+        } else {
+            self.put_file_session(item, reader, size).await
+        }
+    }
+
+    async fn put_file_simple(
+        &self,
+        item: ItemLocation<'_>,
+        mut reader: impl tokio::io::AsyncRead + Unpin,
+        size: u64,
+    ) -> Result<DriveItem, Error> {
+        let mut buf = Vec::with_capacity(size as usize);
+        reader.read_to_end(&mut buf).await.map_err(Error::Io)?;
+
+        #[cfg(feature = "auto-refresh")]
+        self.refresh_if_expired().await?;
+        Ok(self.drive.read().await.upload_small(item, buf).await?)
+    }
+
+    async fn put_file_session(
+        &self,
+        item: ItemLocation<'_>,
+        mut reader: impl tokio::io::AsyncRead + Unpin,
+        size: u64,
+    ) -> Result<DriveItem, Error> {
+        #[cfg(feature = "auto-refresh")]
+        self.refresh_if_expired().await?;
+        let session = self
+            .drive
+            .read()
+            .await
+            .new_upload_session(item, Default::default())
+            .await?;
+
+        let mut uploaded = 0u64;
+        let mut buf = vec![0u8; UPLOAD_CHUNK_SIZE as usize];
+        loop {
+            let chunk_len = std::cmp::min(UPLOAD_CHUNK_SIZE, size - uploaded) as usize;
+            reader
+                .read_exact(&mut buf[..chunk_len])
+                .await
+                .map_err(Error::Io)?;
+
+            let range = uploaded..uploaded + chunk_len as u64;
+            let mut attempt = 0;
+            let result = loop {
+                match session
+                    .upload_part(&self.client, buf[..chunk_len].to_vec(), range.clone(), size)
+                    .await
+                {
+                    Ok(item) => break item,
+                    Err(e) if attempt < UPLOAD_CHUNK_RETRIES && is_server_error(&e) => {
+                        attempt += 1;
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            };
+            uploaded += chunk_len as u64;
+
+            if let Some(item) = result {
+                return Ok(item);
+            }
+        }
+    }
+}
+
+fn is_server_error(e: &onedrive_api::Error) -> bool {
+    e.status_code()
+        .map(|status| status.is_server_error())
+        .unwrap_or(false)
 }
 
 #[derive(Debug)]
@@ -184,6 +374,9 @@ pub struct OneDriveProvider {
     pub(crate) extension: String,
     client: Client,
     albums: HashMap<String, String>, // album_id => path without prefix '/'
+    delta_link: Option<String>,
+    readers: tokio::sync::Mutex<HashMap<String, Arc<ChunkedReader>>>, // item path => reader
+    item_cache: RwLock<HashMap<String, CachedItem>>,                 // item path => cached metadata
 }
 
 impl OneDriveProvider {
@@ -202,6 +395,9 @@ impl OneDriveProvider {
             extension: String::from("flac"),
             client,
             albums: HashMap::new(),
+            delta_link: None,
+            readers: tokio::sync::Mutex::new(HashMap::new()),
+            item_cache: RwLock::new(HashMap::new()),
         }
     }
 
@@ -218,35 +414,135 @@ impl OneDriveProvider {
         Ok(p)
     }
 
+    /// Returns the opaque delta token, if any, used by [`reload_albums`](Self::reload_albums)
+    /// to sync incrementally. Persist this across restarts to avoid a full re-scan.
+    pub fn delta_link(&self) -> Option<&str> {
+        self.delta_link.as_deref()
+    }
+
+    /// Restores a delta token previously returned by [`delta_link`](Self::delta_link),
+    /// e.g. one loaded from disk on startup.
+    pub fn set_delta_link(&mut self, delta_link: Option<String>) {
+        self.delta_link = delta_link;
+    }
+
+    /// `self.path` without its leading `/`, the same absolute-from-drive-root
+    /// form stored as an album's `base` in `self.albums`.
+    fn root_path(&self) -> String {
+        self.path.trim_start_matches('/').to_string()
+    }
+
+    /// Syncs the album map with the drive, applying only what changed since the last
+    /// call via the Graph delta API. Falls back to [`full_reload`](Self::full_reload)
+    /// when no delta token has been stored yet.
     pub async fn reload_albums(&mut self) -> Result<(), Error> {
-        let items = self
-            .drive
-            .list_children(ItemLocation::from_path(&self.path).ok_or(ProviderError::InvalidPath)?)
-            .await?;
-        let albums = items.into_iter().filter_map(|item| {
-            Some((
-                item.name.clone()?,
-                item.parent_reference?["path"]
-                    .as_str()?
-                    .split('/')
-                    .skip_while(|c| *c != "root:")
-                    .skip(1)
-                    .collect(), // get parent path
-            ))
-        });
+        match self.delta_link.take() {
+            Some(delta_link) => {
+                let mut fetcher = self.drive.track_changes_from_delta_url(&delta_link).await?;
+                self.apply_delta(&mut fetcher).await
+            }
+            None => self.full_reload().await,
+        }
+    }
 
+    /// Discards any stored delta token and rebuilds the album map from scratch.
+    pub async fn full_reload(&mut self) -> Result<(), Error> {
         self.albums.clear();
-        self.albums.extend(albums);
+        let location = ItemLocation::from_path(&self.path).ok_or(ProviderError::InvalidPath)?;
+        let mut fetcher = self.drive.track_changes_from_initial(location).await?;
+        self.apply_delta(&mut fetcher).await
+    }
+
+    async fn apply_delta(&mut self, fetcher: &mut onedrive_api::ListChildrenFetcher) -> Result<(), Error> {
+        while let Some(items) = self.drive.fetch_delta_page(fetcher).await? {
+            for item in items {
+                self.apply_delta_item(item);
+            }
+        }
+        self.delta_link = Some(self.drive.fetch_delta_url(fetcher).await?);
+        // Album folders may have moved, so any cached URL or size could now be stale.
+        self.clear_cache().await;
         Ok(())
     }
 
+    /// Applies a single delta-page item to `self.albums`, ignoring anything
+    /// that isn't a direct child of `self.path` (discs, tracks, covers, ...).
+    /// The Graph delta endpoint is recursive over the whole tracked subtree,
+    /// but `albums` only ever held direct children, the same scope
+    /// [`list_children`](OneDriveClient::list_children) gave the old full scan.
+    fn apply_delta_item(&mut self, item: DriveItem) {
+        let Some(name) = item.name else {
+            return;
+        };
+        // Deletions may arrive without a resolvable parentReference; remove by
+        // name regardless of scope (a no-op if it was never a tracked album).
+        if item.deleted.is_some() {
+            self.albums.remove(&name);
+            return;
+        }
+        let Some(parent_path) = parent_path_of(&item) else {
+            return;
+        };
+        if parent_path != self.root_path() {
+            return;
+        }
+        self.albums.insert(name, parent_path);
+    }
+
     /// Returns an onedrive download url of requested path and its size
     pub async fn file_url(&self, path: &str) -> Result<(String, usize), Error> {
+        let item = self.cached_item(path).await?;
+        Ok((item.download_url, item.size as usize))
+    }
+
+    /// Returns the cached `get_item` lookup for `path`, re-resolving it from
+    /// the drive if there is no entry or it is older than [`ITEM_CACHE_TTL`].
+    ///
+    /// Also selects the `audio` facet so [`Mp3OnedriveProvider`](crate::mp3::Mp3OnedriveProvider)
+    /// and [`MultiFormatOneDriveProvider`](crate::multi_format::MultiFormatOneDriveProvider)
+    /// get a track's duration in the same request that confirms it exists.
+    pub(crate) async fn cached_item(&self, path: &str) -> Result<CachedItem, Error> {
+        if let Some(item) = self.item_cache.read().await.get(path) {
+            if item.fetched_at.elapsed() < ITEM_CACHE_TTL {
+                return Ok(item.clone());
+            }
+        }
+
         let location = ItemLocation::from_path(path).ok_or(ProviderError::InvalidPath)?;
-        let item = self.drive.get_item(location, Default::default()).await?;
+        let item = self
+            .drive
+            .get_item(
+                location,
+                ObjectOption::new().select(&[DriveItemField::audio]),
+            )
+            .await?;
         let download_url = item.download_url.ok_or(ProviderError::FileNotFound)?;
-        let size = item.size.unwrap_or_default();
-        Ok((download_url, size as usize))
+        let size = item.size.unwrap_or_default() as u64;
+        let duration = item
+            .audio
+            .as_ref()
+            .and_then(|audio| audio.get("duration"))
+            .and_then(|duration| duration.as_u64());
+
+        let cached = CachedItem {
+            download_url,
+            size,
+            duration,
+            fetched_at: Instant::now(),
+        };
+        self.item_cache
+            .write()
+            .await
+            .insert(path.to_string(), cached.clone());
+        Ok(cached)
+    }
+
+    /// Drops every cached download URL, item lookup and [`ChunkedReader`],
+    /// forcing the next request for each path to re-resolve it from the
+    /// drive instead of serving a stale size or URL after a reload/delta.
+    pub async fn clear_cache(&self) {
+        self.item_cache.write().await.clear();
+        self.readers.lock().await.clear();
     }
 
     /// Returns an onedrive download url of requested audio and its size.
@@ -275,6 +571,83 @@ impl OneDriveProvider {
         };
         self.file_url(&path).await.map(|(url, _)| url)
     }
+
+    /// Uploads audio content for `album_id`/`disc_id`/`track_id`, creating the album
+    /// and disc folders first if they do not exist yet.
+    ///
+    /// If `album_id` is not already known, it is placed directly under the
+    /// provider root.
+    pub async fn upload_audio(
+        &self,
+        album_id: &str,
+        disc_id: NonZeroU8,
+        track_id: NonZeroU8,
+        reader: impl tokio::io::AsyncRead + Unpin,
+        size: u64,
+    ) -> Result<(), Error> {
+        let base = self.albums.get(album_id).cloned().unwrap_or_else(|| self.root_path());
+        self.ensure_directory(&base, album_id).await?;
+        let album_path = join_path(&base, album_id);
+        self.ensure_directory(&album_path, &disc_id.to_string())
+            .await?;
+
+        let path = format_audio_path(&base, album_id, disc_id, track_id, &self.extension);
+        let location = ItemLocation::from_path(&path).ok_or(ProviderError::InvalidPath)?;
+        self.drive.put_file(location, reader, size).await?;
+        Ok(())
+    }
+
+    /// Uploads a cover image for `album_id` (or its `disc_id`), creating the album
+    /// and disc folders first if they do not exist yet.
+    ///
+    /// If `album_id` is not already known, it is placed directly under the
+    /// provider root.
+    pub async fn upload_cover(
+        &self,
+        album_id: &str,
+        disc_id: Option<NonZeroU8>,
+        reader: impl tokio::io::AsyncRead + Unpin,
+        size: u64,
+    ) -> Result<(), Error> {
+        let base = self.albums.get(album_id).cloned().unwrap_or_else(|| self.root_path());
+        self.ensure_directory(&base, album_id).await?;
+        if let Some(disc_id) = disc_id {
+            let album_path = join_path(&base, album_id);
+            self.ensure_directory(&album_path, &disc_id.to_string())
+                .await?;
+        }
+
+        let path = format_cover_path(&base, album_id, disc_id);
+        let location = ItemLocation::from_path(&path).ok_or(ProviderError::InvalidPath)?;
+        self.drive.put_file(location, reader, size).await?;
+        Ok(())
+    }
+
+    /// Creates `name` under `parent_path`, an absolute-from-drive-root path in
+    /// the same form as an album's `base` in `self.albums` (already includes
+    /// `self.path`), ignoring the case where the folder already exists.
+    async fn ensure_directory(&self, parent_path: &str, name: &str) -> Result<(), Error> {
+        let parent_path = format!("/{parent_path}");
+        let parent = ItemLocation::from_path(&parent_path).ok_or(ProviderError::InvalidPath)?;
+        match self.drive.create_directory(parent, name).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.status_code() == Some(reqwest::StatusCode::CONFLICT) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the cached [`ChunkedReader`] for `path`, creating one on first use
+    /// so repeated seeks into the same track reuse downloaded chunks and the
+    /// resolved download URL instead of starting from scratch each time.
+    pub(crate) async fn chunked_reader(&self, path: &str, size: u64) -> Arc<ChunkedReader> {
+        let mut readers = self.readers.lock().await;
+        if let Some(reader) = readers.get(path) {
+            return Arc::clone(reader);
+        }
+        let reader = ChunkedReader::new(Arc::clone(&self.drive), self.client.clone(), path.to_string(), size);
+        readers.insert(path.to_string(), Arc::clone(&reader));
+        reader
+    }
 }
 
 #[async_trait::async_trait]
@@ -296,21 +669,27 @@ impl AnniProvider for OneDriveProvider {
             range.start,
             range.end
         );
-        let (url, size) = self.audio_url(album_id, disc_id, track_id).await?;
+        let path = match self.albums.get(album_id) {
+            Some(p) => format_audio_path(p, album_id, disc_id, track_id, &self.extension),
+            None => return Err(ProviderError::FileNotFound.into()),
+        };
+        let (_, size) = self.file_url(&path).await?;
         log::debug!("audio {album_id}/{disc_id}/{track_id} has a size of {size}");
-        let req = self.client.get(url);
-        let req = match range.to_range_header() {
-            Some(h) => req.header(RANGE, h),
-            None => req,
+
+        let reader = self.chunked_reader(&path, size as u64).await;
+        let start = range.start;
+        reader
+            .fetch_blocking(start..start + 1)
+            .await
+            .map_err(|_| ProviderError::GeneralError)?;
+
+        let handle = reader.open(start);
+        let range = Range {
+            start,
+            end: range.end,
+            total: Some(size as u64),
         };
-        let resp = req.send().await?;
-        let range = content_range_to_range(
-            resp.headers()
-                .get(CONTENT_RANGE)
-                .and_then(|v| v.to_str().ok()),
-        );
-        let reader = StreamReader::new(resp.bytes_stream().map(to_io_error));
-        let (duration, reader) = info::read_duration(Box::pin(reader), range).await?;
+        let (duration, reader) = info::read_duration(Box::pin(handle), range).await?;
         Ok(AudioResourceReader {
             info: AudioInfo {
                 extension: self.extension.clone(),
@@ -345,6 +724,9 @@ impl AnniProvider for OneDriveProvider {
 pub enum Error {
     ProviderError(ProviderError),
     OneDriveError(onedrive_api::Error),
+    /// Reading upload content from the caller-supplied reader failed, e.g. the
+    /// source disconnected or was shorter than the declared `size`.
+    Io(std::io::Error),
 }
 
 impl From<ProviderError> for Error {
@@ -363,7 +745,7 @@ impl From<Error> for ProviderError {
     fn from(value: Error) -> Self {
         match value {
             Error::ProviderError(e) => e,
-            Error::OneDriveError(_) => ProviderError::GeneralError,
+            Error::OneDriveError(_) | Error::Io(_) => ProviderError::GeneralError,
         }
     }
 }
@@ -373,38 +755,40 @@ impl Display for Error {
         match self {
             Self::ProviderError(e) => write!(f, "{e}"),
             Self::OneDriveError(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
-fn content_range_to_range(content_range: Option<&str>) -> Range {
-    match content_range {
-        Some(content_range) => {
-            // if content range header is invalid, return the full range
-            if content_range.len() <= 6 {
-                return Range::FULL;
-            }
-
-            // else, parse the range
-            // Content-Range: bytes 0-1023/10240
-            //                      | offset = 6
-            let content_range = &content_range[6..];
-            let (from, content_range) =
-                content_range.split_once('-').unwrap_or((content_range, ""));
-            let (to, total) = content_range.split_once('/').unwrap_or((content_range, ""));
-
-            Range {
-                start: from.parse().unwrap_or(0),
-                end: to.parse().ok(),
-                total: total.parse().ok(),
-            }
+impl Error {
+    /// True if this error means the requested item doesn't exist, as opposed to
+    /// a transient, auth, or other failure that callers should see instead of a
+    /// misleading not-found.
+    pub(crate) fn is_not_found(&self) -> bool {
+        match self {
+            Self::ProviderError(ProviderError::FileNotFound) => true,
+            Self::OneDriveError(e) => e.status_code() == Some(reqwest::StatusCode::NOT_FOUND),
+            Self::Io(_) => false,
         }
-        None => Range::FULL,
     }
 }
 
+/// Extracts an item's parent path, without the leading `/drive/root:`, the form
+/// the `albums` map is keyed by.
+fn parent_path_of(item: &DriveItem) -> Option<String> {
+    Some(
+        item.parent_reference.as_ref()?["path"]
+            .as_str()?
+            .split('/')
+            .skip_while(|c| *c != "root:")
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
 fn to_io_error<T, E: Into<Box<dyn std::error::Error + Send + Sync>>>(
     r: Result<T, E>,
 ) -> Result<T, std::io::Error> {
@@ -425,6 +809,15 @@ fn format_audio_path(
     }
 }
 
+/// Joins two path segments, dropping either that is empty.
+fn join_path(base: &str, segment: &str) -> String {
+    match (base.is_empty(), segment.is_empty()) {
+        (true, _) => segment.to_string(),
+        (false, true) => base.to_string(),
+        (false, false) => format!("{base}/{segment}"),
+    }
+}
+
 fn format_cover_path(base: &str, album_id: &str, disc_id: Option<NonZeroU8>) -> String {
     let path = match disc_id {
         Some(id) => format!("/{album_id}/{id}/cover.jpg"),
@@ -3,14 +3,8 @@ use std::{borrow::Cow, collections::HashSet, num::NonZeroU8, sync::Arc};
 use anni_provider::{
     AnniProvider, AudioInfo, AudioResourceReader, ProviderError, Range, ResourceReader,
 };
-use onedrive_api::{option::ObjectOption, resource::DriveItemField, ItemLocation};
-use reqwest::header::{CONTENT_RANGE, RANGE};
-use tokio_stream::StreamExt;
-use tokio_util::io::StreamReader;
 
-use crate::{
-    content_range_to_range, format_audio_path, to_io_error, Error, OneDriveClient, OneDriveProvider,
-};
+use crate::{format_audio_path, Error, OneDriveClient, OneDriveProvider};
 
 pub struct Mp3OnedriveProvider {
     provider: OneDriveProvider,
@@ -54,31 +48,13 @@ impl AnniProvider for Mp3OnedriveProvider {
             Some(p) => format_audio_path(p, album_id, disc_id, track_id, &self.provider.extension),
             None => return Err(ProviderError::FileNotFound.into()),
         };
-        let location = ItemLocation::from_path(&path).ok_or(ProviderError::InvalidPath)?;
-
-        let info = self
-            .provider
-            .drive
-            .get_item(
-                location,
-                ObjectOption::new().select(&[DriveItemField::audio]),
-            )
-            .await
-            .map_err(Error::from)?;
 
-        let duration = info
-            .audio
-            .unwrap()
-            .get("duration")
-            .unwrap()
-            .as_u64()
-            .unwrap();
-        let size = info.size.unwrap() as usize;
+        let item = self.provider.cached_item(&path).await?;
 
         Ok(AudioInfo {
             extension: self.provider.extension.clone(),
-            size,
-            duration,
+            size: item.size as usize,
+            duration: item.duration.unwrap_or_default(),
         })
     }
 
@@ -94,53 +70,34 @@ impl AnniProvider for Mp3OnedriveProvider {
             Some(p) => format_audio_path(p, album_id, disc_id, track_id, &self.provider.extension),
             None => return Err(ProviderError::FileNotFound.into()),
         };
-        let location = ItemLocation::from_path(&path).ok_or(ProviderError::InvalidPath)?;
-
-        let item = self
-            .provider
-            .drive
-            .get_item(
-                location,
-                ObjectOption::new().select(&[DriveItemField::audio]),
-            )
-            .await
-            .map_err(Error::from)?;
 
-        let duration = item
-            .audio
-            .unwrap()
-            .get("duration")
-            .unwrap()
-            .as_u64()
-            .unwrap();
-        let size = item.size.unwrap() as usize;
+        let item = self.provider.cached_item(&path).await?;
+        let size = item.size as usize;
 
         let info = AudioInfo {
             extension: self.provider.extension.clone(),
             size,
-            duration,
+            duration: item.duration.unwrap_or_default(),
         };
 
-        let req = self
-            .provider
-            .client
-            .get(item.download_url.ok_or(ProviderError::FileNotFound)?);
-        let req = match range.to_range_header() {
-            Some(h) => req.header(RANGE, h),
-            None => req,
+        let reader = self.provider.chunked_reader(&path, size as u64).await;
+        let start = range.start;
+        reader
+            .fetch_blocking(start..start + 1)
+            .await
+            .map_err(|_| ProviderError::GeneralError)?;
+        let handle = reader.open(start);
+
+        let range = Range {
+            start,
+            end: range.end,
+            total: Some(size as u64),
         };
-        let resp = req.send().await?;
-        let range = content_range_to_range(
-            resp.headers()
-                .get(CONTENT_RANGE)
-                .and_then(|v| v.to_str().ok()),
-        );
-        let reader = StreamReader::new(resp.bytes_stream().map(to_io_error));
 
         Ok(AudioResourceReader {
             info,
             range,
-            reader: Box::pin(reader),
+            reader: Box::pin(handle),
         })
     }
 
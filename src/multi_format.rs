@@ -0,0 +1,173 @@
+use std::{borrow::Cow, collections::HashSet, num::NonZeroU8, sync::Arc};
+
+use crate::{format_audio_path, Error, OneDriveClient, OneDriveProvider};
+use anni_provider::{
+    AnniProvider, AudioInfo, AudioResourceReader, ProviderError, Range, ResourceReader,
+};
+
+/// An ordered list of acceptable audio extensions, most preferred first.
+///
+/// Used by [`MultiFormatOneDriveProvider`] to pick the first format available
+/// for a track instead of hard-coding a single extension.
+#[derive(Debug, Clone)]
+pub struct FormatPreference {
+    extensions: Vec<String>,
+}
+
+impl FormatPreference {
+    /// Builds a preference from an explicit, most-preferred-first extension list.
+    pub fn new(extensions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            extensions: extensions.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Only ever serves `flac`.
+    pub fn flac_only() -> Self {
+        Self::new(["flac"])
+    }
+
+    /// Only ever serves `mp3`.
+    pub fn mp3_only() -> Self {
+        Self::new(["mp3"])
+    }
+
+    /// Prefers `flac`, falling back to `mp3`, then `m4a`.
+    pub fn best_available() -> Self {
+        Self::new(["flac", "mp3", "m4a"])
+    }
+
+    fn extensions(&self) -> &[String] {
+        &self.extensions
+    }
+}
+
+/// A provider that serves whichever of a [`FormatPreference`]'s extensions is
+/// present for a given track, instead of assuming a single fixed format.
+///
+/// This lets one provider cover an Anni strict directory whose albums mix
+/// formats, where [`OneDriveProvider`] (always `flac`) or
+/// [`Mp3OnedriveProvider`](crate::mp3::Mp3OnedriveProvider) (always `mp3`)
+/// would each only cover part of the library.
+pub struct MultiFormatOneDriveProvider {
+    provider: OneDriveProvider,
+    formats: FormatPreference,
+}
+
+impl MultiFormatOneDriveProvider {
+    /// `path` should be the root of an [Anni strict directory](https://book.anni.rs/01.audio-convention/09.directory-strict.html).
+    ///
+    /// Panics if layers > 4. See [Anni audio convention](https://book.anni.rs/01.audio-convention/09.directory-strict.html)
+    pub async fn new(
+        drive: Arc<OneDriveClient>,
+        path: String,
+        layers: usize,
+        formats: FormatPreference,
+    ) -> Result<Self, Error> {
+        let mut provider = OneDriveProvider::with_drive(drive, path, layers);
+        provider.reload_albums().await?;
+        Ok(Self { provider, formats })
+    }
+
+    /// Probes `self.formats` in priority order, returning the path and
+    /// [`AudioInfo`] of the first extension that exists for this track.
+    ///
+    /// Goes through [`OneDriveProvider`]'s cached item lookup, so existence
+    /// and metadata are confirmed in a single (and possibly cached) Graph
+    /// request per candidate.
+    async fn probe_audio(
+        &self,
+        album_id: &str,
+        disc_id: NonZeroU8,
+        track_id: NonZeroU8,
+    ) -> anni_provider::Result<(String, AudioInfo)> {
+        let base = match self.provider.albums.get(album_id) {
+            Some(p) => p,
+            None => return Err(ProviderError::FileNotFound.into()),
+        };
+
+        for extension in self.formats.extensions() {
+            let path = format_audio_path(base, album_id, disc_id, track_id, extension);
+            let item = match self.provider.cached_item(&path).await {
+                Ok(item) => item,
+                Err(e) if e.is_not_found() => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            return Ok((
+                path,
+                AudioInfo {
+                    extension: extension.clone(),
+                    size: item.size as usize,
+                    duration: item.duration.unwrap_or_default(),
+                },
+            ));
+        }
+
+        Err(ProviderError::FileNotFound.into())
+    }
+}
+
+#[async_trait::async_trait]
+impl AnniProvider for MultiFormatOneDriveProvider {
+    async fn albums(&self) -> anni_provider::Result<HashSet<Cow<str>>> {
+        self.provider.albums().await
+    }
+
+    /// Get audio info describing basic information of the audio file, probing
+    /// `self.formats` in priority order.
+    async fn get_audio_info(
+        &self,
+        album_id: &str,
+        disc_id: NonZeroU8,
+        track_id: NonZeroU8,
+    ) -> anni_provider::Result<AudioInfo> {
+        let (_, info) = self.probe_audio(album_id, disc_id, track_id).await?;
+        Ok(info)
+    }
+
+    async fn get_audio(
+        &self,
+        album_id: &str,
+        disc_id: NonZeroU8,
+        track_id: NonZeroU8,
+        range: Range,
+    ) -> anni_provider::Result<AudioResourceReader> {
+        let (path, info) = self.probe_audio(album_id, disc_id, track_id).await?;
+        let size = info.size as u64;
+
+        let reader = self.provider.chunked_reader(&path, size).await;
+        let start = range.start;
+        reader
+            .fetch_blocking(start..start + 1)
+            .await
+            .map_err(|_| ProviderError::GeneralError)?;
+        let handle = reader.open(start);
+
+        let range = Range {
+            start,
+            end: range.end,
+            total: Some(size),
+        };
+
+        Ok(AudioResourceReader {
+            info,
+            range,
+            reader: Box::pin(handle),
+        })
+    }
+
+    /// Returns a cover of corresponding album
+    async fn get_cover(
+        &self,
+        album_id: &str,
+        disc_id: Option<NonZeroU8>,
+    ) -> anni_provider::Result<ResourceReader> {
+        self.provider.get_cover(album_id, disc_id).await
+    }
+
+    /// Reloads the provider for new albums
+    async fn reload(&mut self) -> anni_provider::Result<()> {
+        self.provider.reload().await
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    ops::Range as ByteRange,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use onedrive_api::ItemLocation;
+use reqwest::{header::RANGE, Client};
+use tokio::{
+    io::{AsyncRead, ReadBuf},
+    sync::{Mutex, Notify, RwLock},
+};
+
+use crate::OneDriveClient;
+
+/// Size of each downloaded block. Chosen to keep the waste from an arbitrary
+/// seek small while still amortizing per-request overhead.
+const CHUNK_SIZE: u64 = 512 * 1024;
+
+/// How many chunks ahead of the current read offset to prefetch in the background.
+const READAHEAD_CHUNKS: u64 = 4;
+
+/// OneDrive pre-authenticated download URLs stay valid for roughly an hour;
+/// re-resolve a bit before that so a download never races expiry mid-chunk.
+const DOWNLOAD_URL_TTL: Duration = Duration::from_secs(50 * 60);
+
+#[derive(Clone, Debug)]
+enum ChunkState {
+    InFlight,
+    Ready(Arc<[u8]>),
+}
+
+/// A block-oriented reader over a single OneDrive item.
+///
+/// The file is split into fixed-size chunks; downloaded chunks are cached so
+/// that seeking within the same item (a fresh [`ChunkedReaderHandle`] at a new
+/// offset) can reuse anything already fetched, and [`fetch`](Self::fetch) lets
+/// a handle prefetch upcoming chunks in the background while it reads. A
+/// single resolved download URL is reused across chunk requests and only
+/// re-resolved once it nears expiry, so a long read doesn't pay for a new
+/// Graph `get_item` call per chunk.
+#[derive(Debug)]
+pub struct ChunkedReader {
+    drive: Arc<OneDriveClient>,
+    client: Client,
+    path: String,
+    size: u64,
+    cached_url: RwLock<Option<(String, Instant)>>,
+    chunks: Mutex<HashMap<u64, ChunkState>>,
+    notify: Notify,
+}
+
+impl ChunkedReader {
+    pub fn new(drive: Arc<OneDriveClient>, client: Client, path: String, size: u64) -> Arc<Self> {
+        Arc::new(Self {
+            drive,
+            client,
+            path,
+            size,
+            cached_url: RwLock::new(None),
+            chunks: Mutex::new(HashMap::new()),
+            notify: Notify::new(),
+        })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn chunk_index(&self, offset: u64) -> u64 {
+        offset / CHUNK_SIZE
+    }
+
+    fn last_chunk_index(&self) -> u64 {
+        self.size.saturating_sub(1) / CHUNK_SIZE
+    }
+
+    fn chunk_byte_range(&self, index: u64) -> ByteRange<u64> {
+        let start = index * CHUNK_SIZE;
+        let end = (start + CHUNK_SIZE).min(self.size);
+        start..end
+    }
+
+    /// Returns a handle that reads the item starting at `offset`.
+    pub fn open(self: &Arc<Self>, offset: u64) -> ChunkedReaderHandle {
+        ChunkedReaderHandle {
+            reader: Arc::clone(self),
+            offset,
+            state: ReadState::Idle,
+        }
+    }
+
+    /// Hints that `range` will be needed soon: downloads any chunk it overlaps
+    /// that is neither downloaded nor already in flight, without waiting for them.
+    pub fn fetch(self: &Arc<Self>, range: ByteRange<u64>) {
+        for index in self.chunks_in(range) {
+            let reader = Arc::clone(self);
+            tokio::spawn(async move {
+                let _ = reader.ensure_chunk(index).await;
+            });
+        }
+    }
+
+    /// Blocks until every chunk overlapping `range` is resident, re-requesting
+    /// any chunk that is neither downloaded nor in flight so a failed download
+    /// can be recovered from.
+    pub async fn fetch_blocking(&self, range: ByteRange<u64>) -> std::io::Result<()> {
+        for index in self.chunks_in(range) {
+            self.ensure_chunk(index).await?;
+        }
+        Ok(())
+    }
+
+    fn chunks_in(&self, range: ByteRange<u64>) -> ByteRange<u64> {
+        if range.end <= range.start || range.start >= self.size {
+            return 0..0;
+        }
+        let start = self.chunk_index(range.start);
+        let end = self.chunk_index((range.end - 1).min(self.size.saturating_sub(1)));
+        start..end + 1
+    }
+
+    async fn ensure_chunk(&self, index: u64) -> std::io::Result<Arc<[u8]>> {
+        loop {
+            let mut chunks = self.chunks.lock().await;
+            match chunks.get(&index).cloned() {
+                Some(ChunkState::Ready(data)) => return Ok(data),
+                Some(ChunkState::InFlight) => {
+                    // Register for notification before releasing the lock, so a
+                    // notify_waiters() that lands between the drop and the await
+                    // below can't be missed.
+                    let notified = self.notify.notified();
+                    drop(chunks);
+                    notified.await;
+                    continue;
+                }
+                None => {
+                    chunks.insert(index, ChunkState::InFlight);
+                    break;
+                }
+            }
+        }
+
+        match self.download_chunk(index).await {
+            Ok(data) => {
+                self.chunks
+                    .lock()
+                    .await
+                    .insert(index, ChunkState::Ready(Arc::clone(&data)));
+                self.notify.notify_waiters();
+                Ok(data)
+            }
+            Err(e) => {
+                // Drop the in-flight marker so the next caller re-requests this chunk.
+                self.chunks.lock().await.remove(&index);
+                self.notify.notify_waiters();
+                Err(e)
+            }
+        }
+    }
+
+    async fn download_chunk(&self, index: u64) -> std::io::Result<Arc<[u8]>> {
+        let range = self.chunk_byte_range(index);
+        if range.start >= range.end {
+            return Ok(Arc::from([]));
+        }
+
+        let url = self.download_url().await?;
+        let header = format!("bytes={}-{}", range.start, range.end - 1);
+        let resp = self
+            .client
+            .get(url)
+            .header(RANGE, header)
+            .send()
+            .await
+            .map_err(to_io_error)?;
+        let bytes = resp.bytes().await.map_err(to_io_error)?;
+        Ok(Arc::from(bytes.as_ref()))
+    }
+
+    async fn download_url(&self) -> std::io::Result<String> {
+        if let Some(url) = self.fresh_cached_url().await {
+            return Ok(url);
+        }
+
+        let mut cached_url = self.cached_url.write().await;
+        if let Some((url, fetched_at)) = cached_url.as_ref() {
+            if fetched_at.elapsed() < DOWNLOAD_URL_TTL {
+                return Ok(url.clone());
+            }
+        }
+
+        let location =
+            ItemLocation::from_path(&self.path).ok_or_else(|| invalid_path_error(&self.path))?;
+        let url = self
+            .drive
+            .get_item_download_url(location)
+            .await
+            .map_err(to_io_error)?;
+        *cached_url = Some((url.clone(), Instant::now()));
+        Ok(url)
+    }
+
+    async fn fresh_cached_url(&self) -> Option<String> {
+        let cached_url = self.cached_url.read().await;
+        let (url, fetched_at) = cached_url.as_ref()?;
+        (fetched_at.elapsed() < DOWNLOAD_URL_TTL).then(|| url.clone())
+    }
+}
+
+enum ReadState {
+    Idle,
+    Pending(Pin<Box<dyn Future<Output = std::io::Result<Arc<[u8]>>> + Send>>),
+}
+
+/// An [`AsyncRead`] over a [`ChunkedReader`] starting at a fixed offset,
+/// prefetching the next [`READAHEAD_CHUNKS`] chunks as it goes.
+pub struct ChunkedReaderHandle {
+    reader: Arc<ChunkedReader>,
+    offset: u64,
+    state: ReadState,
+}
+
+impl ChunkedReaderHandle {
+    fn prefetch_ahead(&self) {
+        let index = self.reader.chunk_index(self.offset);
+        let ahead_start = (index + 1) * CHUNK_SIZE;
+        let ahead_end =
+            ((index + READAHEAD_CHUNKS).min(self.reader.last_chunk_index()) + 1) * CHUNK_SIZE;
+        if ahead_start < ahead_end {
+            self.reader.fetch(ahead_start..ahead_end);
+        }
+    }
+}
+
+impl AsyncRead for ChunkedReaderHandle {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if this.offset >= this.reader.size {
+            return Poll::Ready(Ok(()));
+        }
+
+        loop {
+            match &mut this.state {
+                ReadState::Idle => {
+                    this.prefetch_ahead();
+                    let reader = Arc::clone(&this.reader);
+                    let index = reader.chunk_index(this.offset);
+                    this.state =
+                        ReadState::Pending(Box::pin(
+                            async move { reader.ensure_chunk(index).await },
+                        ));
+                }
+                ReadState::Pending(fut) => {
+                    let result = match fut.as_mut().poll(cx) {
+                        Poll::Ready(result) => result,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.state = ReadState::Idle;
+                    let data = result?;
+
+                    let index = this.reader.chunk_index(this.offset);
+                    let chunk_start = index * CHUNK_SIZE;
+                    let pos_in_chunk = (this.offset - chunk_start) as usize;
+                    let available = &data[pos_in_chunk..];
+                    let n = available.len().min(buf.remaining());
+                    buf.put_slice(&available[..n]);
+                    this.offset += n as u64;
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+    }
+}
+
+fn invalid_path_error(path: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("invalid item path: {path}"),
+    )
+}
+
+fn to_io_error<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e)
+}
@@ -0,0 +1,68 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use tokio::{fs, io::AsyncWriteExt};
+
+/// Persists the OneDrive refresh token across restarts.
+///
+/// OneDrive rotates the refresh token on every `login_with_refresh_token`, so a
+/// long-running [`OneDriveClient`](crate::OneDriveClient) needs somewhere durable
+/// to keep the latest one or it loses access after a restart.
+#[async_trait::async_trait]
+pub trait TokenStore: std::fmt::Debug + Send + Sync {
+    /// Loads a previously saved refresh token, if any.
+    async fn load(&self) -> Option<String>;
+
+    /// Saves the refresh token obtained from the most recent login.
+    async fn save(&self, refresh_token: &str);
+}
+
+/// A [`TokenStore`] that keeps the refresh token in a plain file at the path
+/// given to [`FileTokenStore::new`], written atomically via a temp-file-then-rename so a crash
+/// mid-write can't corrupt it.
+#[derive(Debug)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenStore for FileTokenStore {
+    async fn load(&self) -> Option<String> {
+        let content = fs::read_to_string(&self.path).await.ok()?;
+        let token = content.trim();
+        (!token.is_empty()).then(|| token.to_string())
+    }
+
+    async fn save(&self, refresh_token: &str) {
+        if let Err(e) = self.save_atomic(refresh_token).await {
+            log::warn!(
+                "failed to persist refresh token to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+impl FileTokenStore {
+    async fn save_atomic(&self, refresh_token: &str) -> std::io::Result<()> {
+        let tmp_path = tmp_path(&self.path);
+        let mut file = fs::File::create(&tmp_path).await?;
+        file.write_all(refresh_token.as_bytes()).await?;
+        file.flush().await?;
+        fs::rename(&tmp_path, &self.path).await
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name: OsString = path.file_name().unwrap_or_default().to_owned();
+    name.push(".tmp");
+    path.with_file_name(name)
+}